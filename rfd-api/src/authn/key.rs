@@ -1,3 +1,4 @@
+use chrono::Utc;
 use hex::FromHexError;
 use rand::{rngs::OsRng, RngCore};
 use thiserror::Error;
@@ -5,9 +6,35 @@ use uuid::Uuid;
 
 use super::{Signer, SigningKeyError};
 
+/// The leading bytes that mark a key as using the scoped layout
+/// (`[magic:8][uuid:16][expires_at:8][permissions][nonce]`). Keys issued before this layout
+/// existed have no marker at all — just `[uuid:16][nonce]`, where the leading bytes are simply
+/// the first bytes of a random UUID. A single marker byte would collide with a real legacy UUID
+/// byte about 1 time in 256, misrouting an active legacy key into the scoped branch; an 8-byte
+/// magic sequence makes that collision (1 in 2^64) not worth guarding against, so a missing/
+/// non-matching marker reliably means "legacy, unscoped" rather than "unsupported version".
+const SCOPED_MAGIC: [u8; 8] = *b"RFDKEYv2";
+
+const LEGACY_ID_LEN: usize = 16;
+/// `[magic:8][uuid:16]` for the scoped layout.
+const HEADER_LEN: usize = SCOPED_MAGIC.len() + LEGACY_ID_LEN;
+/// `[expires_at:8]` follows the header on scoped keys, before the permission list.
+const EXPIRES_AT_LEN: usize = 8;
+/// The shortest a scoped key can legitimately be: header, expiry, and at least the permission
+/// count byte. Anything claiming to be scoped but shorter than this is truncated/corrupt and
+/// must be rejected outright rather than silently treated as having no expiry or permissions.
+const SCOPED_MIN_LEN: usize = HEADER_LEN + EXPIRES_AT_LEN + 1;
+
+#[derive(Debug)]
+struct ScopedMeta {
+    expires_at: i64,
+    permissions: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct RawApiKey {
     clear: Vec<u8>,
+    scoped: Option<ScopedMeta>,
 }
 
 #[derive(Debug, Error)]
@@ -16,6 +43,8 @@ pub enum ApiKeyError {
     Decode(#[from] FromHexError),
     #[error("Failed to parse API key")]
     FailedToParse,
+    #[error("API key has expired")]
+    Expired,
     #[error("Signature is malformed: {0}")]
     MalformedSignature(#[from] rsa::signature::Error),
     #[error("Failed to sign API key: {0}")]
@@ -25,20 +54,66 @@ pub enum ApiKeyError {
 }
 
 impl RawApiKey {
-    // Generate a new API key
+    // Generate a new API key that carries only an id; permissions and expiry require a storage
+    // lookup after the signature is verified. This is the same wire layout that has always been
+    // issued: `[uuid:16][random nonce]`, with no version byte.
     pub fn generate<const N: usize>(id: &Uuid) -> Self {
         // Generate random data to extend the token id with
         let mut token_raw = [0; N];
         OsRng.fill_bytes(&mut token_raw);
 
         let mut clear = id.as_bytes().to_vec();
-        clear.append(&mut token_raw.to_vec());
+        clear.extend_from_slice(&token_raw);
 
-        Self { clear }
+        Self {
+            clear,
+            scoped: None,
+        }
+    }
+
+    /// Generate a new API key that embeds its own expiry and permissions, so that a signature
+    /// check alone is enough to make an authorization decision without a storage lookup.
+    pub fn generate_scoped<const N: usize>(
+        id: &Uuid,
+        expires_at: i64,
+        permissions: &[String],
+    ) -> Self {
+        let mut token_raw = [0; N];
+        OsRng.fill_bytes(&mut token_raw);
+
+        let mut clear = SCOPED_MAGIC.to_vec();
+        clear.extend_from_slice(id.as_bytes());
+        clear.extend_from_slice(&expires_at.to_be_bytes());
+        clear.extend_from_slice(&encode_permissions(permissions));
+        clear.extend_from_slice(&token_raw);
+
+        Self {
+            clear,
+            scoped: Some(ScopedMeta {
+                expires_at,
+                permissions: permissions.to_vec(),
+            }),
+        }
     }
 
     pub fn id(&self) -> &[u8] {
-        &self.clear[0..16]
+        if self.scoped.is_some() {
+            &self.clear[SCOPED_MAGIC.len()..HEADER_LEN]
+        } else {
+            &self.clear[0..LEGACY_ID_LEN]
+        }
+    }
+
+    /// The embedded expiry, in unix seconds, for keys generated via [`Self::generate_scoped`].
+    /// Legacy, unscoped keys have no embedded expiry and rely on a storage lookup instead.
+    pub fn expires_at(&self) -> Option<i64> {
+        self.scoped.as_ref().map(|meta| meta.expires_at)
+    }
+
+    /// The embedded permission scopes for keys generated via [`Self::generate_scoped`]. Legacy,
+    /// unscoped keys have no embedded permissions and rely on a storage lookup instead.
+    pub fn permissions(&self) -> Option<Vec<String>> {
+        self.scoped.as_ref().map(|meta| meta.permissions.clone())
     }
 
     pub async fn sign(self, signer: &dyn Signer) -> Result<SignedApiKey, ApiKeyError> {
@@ -52,6 +127,12 @@ impl RawApiKey {
     }
 
     pub fn verify(&self, signer: &dyn Signer, signature: &[u8]) -> Result<bool, ApiKeyError> {
+        if let Some(expires_at) = self.expires_at() {
+            if expires_at < Utc::now().timestamp() {
+                return Err(ApiKeyError::Expired);
+            }
+        }
+
         let signature = hex::decode(signature)?;
         Ok(signer
             .verify(&self.clear, &signature)
@@ -60,18 +141,84 @@ impl RawApiKey {
     }
 }
 
+/// Encode a permission list as `[count:1][len:1][scope bytes]...`. Kept as a simple length
+/// prefixed list rather than a bitset since the set of permission strings is open ended.
+fn encode_permissions(permissions: &[String]) -> Vec<u8> {
+    let mut encoded = vec![permissions.len() as u8];
+
+    for permission in permissions {
+        let bytes = permission.as_bytes();
+        encoded.push(bytes.len() as u8);
+        encoded.extend_from_slice(bytes);
+    }
+
+    encoded
+}
+
+fn decode_permissions(bytes: &[u8]) -> Result<Vec<String>, ApiKeyError> {
+    let count = *bytes.first().ok_or(ApiKeyError::FailedToParse)? as usize;
+    let mut cursor = 1;
+    let mut permissions = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let len = *bytes.get(cursor).ok_or(ApiKeyError::FailedToParse)? as usize;
+        let start = cursor + 1;
+        let end = start + len;
+        let slice = bytes.get(start..end).ok_or(ApiKeyError::FailedToParse)?;
+        permissions.push(
+            std::str::from_utf8(slice)
+                .map_err(|_| ApiKeyError::FailedToParse)?
+                .to_string(),
+        );
+        cursor = end;
+    }
+
+    Ok(permissions)
+}
+
 impl TryFrom<&str> for RawApiKey {
     type Error = ApiKeyError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let decoded = hex::decode(value)?;
 
-        if decoded.len() > 16 {
-            Ok(RawApiKey { clear: decoded })
-        } else {
-            tracing::debug!(len = ?decoded.len(), "API key is too short");
-            Err(ApiKeyError::FailedToParse)
+        // A leading scoped-layout magic sequence is a strong, explicit claim about the layout
+        // (not a byte a legacy key's random id could plausibly produce): validate it fully and
+        // reject outright if it's truncated, rather than falling back to treating it as an
+        // unscoped key (which would silently skip the expiry check).
+        if decoded.starts_with(&SCOPED_MAGIC) {
+            if decoded.len() < SCOPED_MIN_LEN {
+                tracing::debug!(len = ?decoded.len(), "Scoped API key is too short");
+                return Err(ApiKeyError::FailedToParse);
+            }
+
+            let expires_at = i64::from_be_bytes(
+                decoded[HEADER_LEN..HEADER_LEN + EXPIRES_AT_LEN]
+                    .try_into()
+                    .map_err(|_| ApiKeyError::FailedToParse)?,
+            );
+            let permissions = decode_permissions(&decoded[HEADER_LEN + EXPIRES_AT_LEN..])?;
+
+            return Ok(RawApiKey {
+                clear: decoded,
+                scoped: Some(ScopedMeta {
+                    expires_at,
+                    permissions,
+                }),
+            });
+        }
+
+        // Otherwise this is the legacy `[uuid:16][nonce]` layout every previously-issued key
+        // uses, with no version byte to strip.
+        if decoded.len() > LEGACY_ID_LEN {
+            return Ok(RawApiKey {
+                clear: decoded,
+                scoped: None,
+            });
         }
+
+        tracing::debug!(len = ?decoded.len(), "API key is too short");
+        Err(ApiKeyError::FailedToParse)
     }
 }
 
@@ -98,7 +245,7 @@ impl SignedApiKey {
 mod tests {
     use uuid::Uuid;
 
-    use super::RawApiKey;
+    use super::{RawApiKey, SCOPED_MAGIC, SCOPED_MIN_LEN};
     use crate::util::tests::mock_key;
 
     #[tokio::test]
@@ -130,4 +277,90 @@ mod tests {
 
         assert_ne!(signed1.signature(), signed2.signature())
     }
+
+    #[tokio::test]
+    async fn test_scoped_key_round_trips_expiry_and_permissions() {
+        let id = Uuid::new_v4();
+        let signer = mock_key().as_signer().await.unwrap();
+        let expires_at = chrono::Utc::now().timestamp() + 3600;
+        let permissions = vec!["rfd:read".to_string(), "rfd:write".to_string()];
+
+        let raw = RawApiKey::generate_scoped::<8>(&id, expires_at, &permissions);
+        let signed = raw.sign(&*signer).await.unwrap();
+
+        let raw2 = RawApiKey::try_from(signed.key.as_str()).unwrap();
+
+        assert_eq!(raw2.expires_at(), Some(expires_at));
+        assert_eq!(raw2.permissions(), Some(permissions));
+        assert!(raw2.verify(&*signer, signed.signature.as_bytes()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expired_scoped_key_fails_verification() {
+        let id = Uuid::new_v4();
+        let signer = mock_key().as_signer().await.unwrap();
+        let expires_at = chrono::Utc::now().timestamp() - 3600;
+
+        let raw = RawApiKey::generate_scoped::<8>(&id, expires_at, &[]);
+        let signed = raw.sign(&*signer).await.unwrap();
+
+        let raw2 = RawApiKey::try_from(signed.key.as_str()).unwrap();
+
+        assert!(raw2.verify(&*signer, signed.signature.as_bytes()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_unscoped_key_still_verifies() {
+        // Previously-issued keys have no version byte at all; make sure the scoped-key layout
+        // change doesn't lock them out.
+        let id = Uuid::new_v4();
+        let signer = mock_key().as_signer().await.unwrap();
+
+        let raw = RawApiKey::generate::<8>(&id);
+        let signed = raw.sign(&*signer).await.unwrap();
+
+        let raw2 = RawApiKey::try_from(signed.key.as_str()).unwrap();
+
+        assert_eq!(raw2.id(), id.as_bytes());
+        assert_eq!(raw2.expires_at(), None);
+        assert_eq!(raw2.permissions(), None);
+        assert!(raw2.verify(&*signer, signed.signature.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_truncated_scoped_key_is_rejected() {
+        let mut bytes = SCOPED_MAGIC.to_vec();
+        bytes.extend_from_slice(Uuid::new_v4().as_bytes());
+        // One byte short of SCOPED_MIN_LEN: missing the permission count byte.
+        bytes.extend_from_slice(&[0u8; 8]);
+        assert!(bytes.len() < SCOPED_MIN_LEN);
+
+        assert!(RawApiKey::try_from(hex::encode(bytes).as_str()).is_err());
+    }
+
+    #[test]
+    fn test_empty_key_is_rejected() {
+        assert!(RawApiKey::try_from("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_key_colliding_with_scoped_marker_byte_still_verifies() {
+        // Regression guard: a legacy key's id is a fully random UUID, so its leading byte(s) can
+        // coincidentally match what used to be the single-byte scoped marker. Confirm this no
+        // longer misroutes a legacy key into the scoped branch by forcing the collision
+        // directly: a legacy key whose payload happens to start with the same byte the old
+        // single-byte marker used must still parse and verify as legacy.
+        let id = Uuid::new_v4();
+        let signer = mock_key().as_signer().await.unwrap();
+
+        let mut raw = RawApiKey::generate::<8>(&id);
+        raw.clear[0] = SCOPED_MAGIC[0];
+        let signed = raw.sign(&*signer).await.unwrap();
+
+        let raw2 = RawApiKey::try_from(signed.key.as_str()).unwrap();
+
+        assert_eq!(raw2.expires_at(), None);
+        assert_eq!(raw2.permissions(), None);
+        assert!(raw2.verify(&*signer, signed.signature.as_bytes()).unwrap());
+    }
 }
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_kms::{
+    config::Region,
+    primitives::Blob,
+    types::{MessageType, SigningAlgorithmSpec},
+    Client,
+};
+use thiserror::Error;
+
+use crate::config::AsymmetricKey;
+
+use super::{Signer, SigningKeyError};
+
+#[derive(Debug, Error)]
+pub enum KmsSignerError {
+    #[error("Unsupported KMS signing algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("KMS did not return a signature")]
+    MissingSignature,
+    #[error("Expected a kind = \"kms\" key configuration entry")]
+    UnsupportedKeyKind,
+}
+
+/// A [`Signer`] backed by an asymmetric key held in AWS KMS. Sign and verify calls never see the
+/// private key material; they are performed by KMS itself, so the key can live behind an HSM
+/// boundary.
+pub struct KmsSigner {
+    client: Client,
+    kid: String,
+    key_arn: String,
+    signing_algorithm: SigningAlgorithmSpec,
+}
+
+impl KmsSigner {
+    pub fn new(
+        client: Client,
+        kid: String,
+        key_arn: String,
+        signing_algorithm: &str,
+    ) -> Result<Self, KmsSignerError> {
+        let signing_algorithm = parse_signing_algorithm(signing_algorithm)?;
+
+        Ok(Self {
+            client,
+            kid,
+            key_arn,
+            signing_algorithm,
+        })
+    }
+
+    /// Build a `KmsSigner` for a `kind = "kms"` entry in `AppConfig::keys`; any other kind of
+    /// entry is rejected with [`KmsSignerError::UnsupportedKeyKind`]. The KMS client is built
+    /// against the region configured for this key rather than inherited from the environment,
+    /// since a deployment's keys may live in a different region than its other AWS resources.
+    pub async fn from_config(key: &AsymmetricKey) -> Result<Self, KmsSignerError> {
+        let AsymmetricKey::Kms {
+            kid,
+            key_arn,
+            region,
+            signing_algorithm,
+        } = key
+        else {
+            return Err(KmsSignerError::UnsupportedKeyKind);
+        };
+
+        let sdk_config = aws_config::from_env()
+            .region(Region::new(region.clone()))
+            .load()
+            .await;
+
+        Self::new(
+            Client::new(&sdk_config),
+            kid.clone(),
+            key_arn.clone(),
+            signing_algorithm,
+        )
+    }
+}
+
+fn parse_signing_algorithm(value: &str) -> Result<SigningAlgorithmSpec, KmsSignerError> {
+    SigningAlgorithmSpec::from_str(value)
+        .ok()
+        .filter(|spec| spec != &SigningAlgorithmSpec::Unknown(value.to_string()))
+        .ok_or_else(|| KmsSignerError::UnsupportedAlgorithm(value.to_string()))
+}
+
+#[async_trait]
+impl Signer for KmsSigner {
+    fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SigningKeyError> {
+        let response = self
+            .client
+            .sign()
+            .key_id(&self.key_arn)
+            .message(Blob::new(message))
+            .message_type(MessageType::Raw)
+            .signing_algorithm(self.signing_algorithm.clone())
+            .send()
+            .await
+            .map_err(|err| SigningKeyError::Signing(err.to_string()))?;
+
+        response
+            .signature
+            .map(|blob| blob.into_inner())
+            .ok_or_else(|| SigningKeyError::Signing(KmsSignerError::MissingSignature.to_string()))
+    }
+
+    async fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), SigningKeyError> {
+        let response = self
+            .client
+            .verify()
+            .key_id(&self.key_arn)
+            .message(Blob::new(message))
+            .message_type(MessageType::Raw)
+            .signature(Blob::new(signature))
+            .signing_algorithm(self.signing_algorithm.clone())
+            .send()
+            .await
+            .map_err(|err| SigningKeyError::Verification(err.to_string()))?;
+
+        if response.signature_valid {
+            Ok(())
+        } else {
+            Err(SigningKeyError::Verification(
+                "KMS reported the signature as invalid".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signing_algorithm_accepts_known_values() {
+        assert_eq!(
+            parse_signing_algorithm("RSASSA_PKCS1_V1_5_SHA_256").unwrap(),
+            SigningAlgorithmSpec::RsassaPkcs1V15Sha256
+        );
+        assert_eq!(
+            parse_signing_algorithm("ECDSA_SHA_256").unwrap(),
+            SigningAlgorithmSpec::EcdsaSha256
+        );
+    }
+
+    #[test]
+    fn test_parse_signing_algorithm_rejects_unknown_value() {
+        assert!(matches!(
+            parse_signing_algorithm("not-a-real-algorithm"),
+            Err(KmsSignerError::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_from_config_rejects_non_kms_key() {
+        let key = AsymmetricKey::Local {
+            kid: "local-1".to_string(),
+            private: "private".to_string(),
+            public: "public".to_string(),
+        };
+
+        assert!(matches!(
+            KmsSigner::from_config(&key).await,
+            Err(KmsSignerError::UnsupportedKeyKind)
+        ));
+    }
+}
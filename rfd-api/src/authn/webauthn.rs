@@ -0,0 +1,366 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{rngs::OsRng, RngCore};
+use thiserror::Error;
+use uuid::Uuid;
+use webauthn_rs::{
+    prelude::{CreationChallengeResponse, Passkey, PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse},
+    Webauthn, WebauthnBuilder,
+};
+
+use super::key::{ApiKeyError, RawApiKey, SignedApiKey};
+use super::Signer;
+
+#[derive(Debug, Error)]
+pub enum WebauthnError {
+    #[error("Failed to construct Webauthn relying party: {0}")]
+    Configuration(webauthn_rs::prelude::WebauthnError),
+    #[error("Registration challenge could not be completed: {0}")]
+    Registration(webauthn_rs::prelude::WebauthnError),
+    #[error("Assertion challenge could not be completed: {0}")]
+    Assertion(webauthn_rs::prelude::WebauthnError),
+    #[error("No in-progress challenge was found for this user")]
+    ChallengeNotFound,
+    #[error("Authenticator signature counter did not increase; the credential may be cloned")]
+    CounterDidNotIncrease,
+    #[error("No credentials are registered for this user")]
+    NoCredentials,
+    #[error("Credential store failure: {0}")]
+    CredentialStore(String),
+    #[error("Failed to issue a session key: {0}")]
+    KeyIssuance(#[from] ApiKeyError),
+}
+
+/// A credential that a user has registered for passwordless / second-factor login. Mirrors the
+/// fields a relying party is required to retain per the WebAuthn spec: the credential id and
+/// public key (bundled as the opaque `Passkey`), plus the signature counter used to detect
+/// cloned authenticators.
+#[derive(Debug, Clone)]
+pub struct RegisteredCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub passkey: Passkey,
+    pub aaguid: Uuid,
+    pub signature_count: u32,
+    pub name: String,
+}
+
+/// Short-lived state for a registration or authentication ceremony that is currently in
+/// progress. This is bound to a single user and must be discarded (and rejected if presented
+/// again) once the ceremony completes or expires.
+pub enum WebauthnChallengeState {
+    Registration(webauthn_rs::prelude::PasskeyRegistration),
+    Authentication(webauthn_rs::prelude::PasskeyAuthentication),
+}
+
+/// Thin wrapper around `webauthn-rs` that produces and verifies the credential-creation and
+/// assertion challenges used by the registration and authentication endpoints.
+pub struct WebauthnAuthenticator {
+    webauthn: Webauthn,
+}
+
+impl WebauthnAuthenticator {
+    pub fn new(rp_id: &str, rp_origin: &str, rp_name: &str) -> Result<Self, WebauthnError> {
+        let origin = rp_origin.parse().map_err(|_| {
+            WebauthnError::Configuration(webauthn_rs::prelude::WebauthnError::Configuration)
+        })?;
+
+        let webauthn = WebauthnBuilder::new(rp_id, &origin)
+            .map_err(WebauthnError::Configuration)?
+            .rp_name(rp_name)
+            .build()
+            .map_err(WebauthnError::Configuration)?;
+
+        Ok(Self { webauthn })
+    }
+
+    /// Begin registering a new passkey for `user_id`, returning the challenge the client must
+    /// answer and the state that must be persisted (bound to this user) until `finish_registration`
+    /// is called.
+    pub fn begin_registration(
+        &self,
+        user_id: Uuid,
+        user_name: &str,
+        existing_credentials: &[RegisteredCredential],
+    ) -> Result<(CreationChallengeResponse, WebauthnChallengeState), WebauthnError> {
+        let exclude_credentials = existing_credentials
+            .iter()
+            .map(|cred| cred.passkey.cred_id().clone())
+            .collect::<Vec<_>>();
+
+        let (challenge, state) = self
+            .webauthn
+            .start_passkey_registration(user_id, user_name, user_name, Some(exclude_credentials))
+            .map_err(WebauthnError::Registration)?;
+
+        Ok((challenge, WebauthnChallengeState::Registration(state)))
+    }
+
+    /// Complete a registration ceremony, producing the credential record that should be stored
+    /// for this user.
+    pub fn finish_registration(
+        &self,
+        user_id: Uuid,
+        name: String,
+        response: &RegisterPublicKeyCredential,
+        state: WebauthnChallengeState,
+    ) -> Result<RegisteredCredential, WebauthnError> {
+        let WebauthnChallengeState::Registration(state) = state else {
+            return Err(WebauthnError::ChallengeNotFound);
+        };
+
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(response, &state)
+            .map_err(WebauthnError::Registration)?;
+
+        Ok(RegisteredCredential {
+            id: Uuid::new_v4(),
+            user_id,
+            credential_id: passkey.cred_id().as_ref().to_vec(),
+            aaguid: passkey.aaguid(),
+            signature_count: 0,
+            passkey,
+            name,
+        })
+    }
+
+    /// Begin an authentication ceremony against a user's already-registered credentials.
+    pub fn begin_authentication(
+        &self,
+        credentials: &[RegisteredCredential],
+    ) -> Result<(RequestChallengeResponse, WebauthnChallengeState), WebauthnError> {
+        let passkeys = credentials
+            .iter()
+            .map(|cred| cred.passkey.clone())
+            .collect::<Vec<_>>();
+
+        let (challenge, state) = self
+            .webauthn
+            .start_passkey_authentication(&passkeys)
+            .map_err(WebauthnError::Assertion)?;
+
+        Ok((challenge, WebauthnChallengeState::Authentication(state)))
+    }
+
+    /// Complete an authentication ceremony, returning the updated signature counter that must be
+    /// persisted back onto the matching `RegisteredCredential`. Rejects the assertion outright if
+    /// the counter did not strictly increase, which indicates the authenticator (or its key
+    /// material) has been cloned; a counter that stays at `0` is accepted, as described on
+    /// [`check_counter_advanced`].
+    pub fn finish_authentication(
+        &self,
+        credential: &RegisteredCredential,
+        response: &PublicKeyCredential,
+        state: WebauthnChallengeState,
+    ) -> Result<u32, WebauthnError> {
+        let WebauthnChallengeState::Authentication(state) = state else {
+            return Err(WebauthnError::ChallengeNotFound);
+        };
+
+        let result = self
+            .webauthn
+            .finish_passkey_authentication(response, &state)
+            .map_err(WebauthnError::Assertion)?;
+
+        let new_count = result.counter();
+        check_counter_advanced(credential.signature_count, new_count)?;
+
+        Ok(new_count)
+    }
+}
+
+/// A signature counter must strictly increase between assertions, with one exception: an
+/// authenticator that does not implement a counter always reports `0`, in which case a repeated
+/// `0` is expected rather than evidence of a cloned credential.
+fn check_counter_advanced(previous: u32, new: u32) -> Result<(), WebauthnError> {
+    if new == 0 && previous == 0 {
+        return Ok(());
+    }
+
+    if new <= previous {
+        return Err(WebauthnError::CounterDidNotIncrease);
+    }
+
+    Ok(())
+}
+
+/// Generate an opaque id used to correlate a pending challenge with the session that started it,
+/// independent of the challenge bytes embedded in the WebAuthn response itself.
+pub fn generate_challenge_binding() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Durable storage for a user's registered credentials.
+#[async_trait]
+pub trait CredentialStore: Send + Sync {
+    async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<RegisteredCredential>, WebauthnError>;
+    async fn insert(&self, credential: RegisteredCredential) -> Result<(), WebauthnError>;
+    async fn update_signature_count(
+        &self,
+        credential_id: Uuid,
+        signature_count: u32,
+    ) -> Result<(), WebauthnError>;
+}
+
+/// Short-lived storage for a challenge that is currently in progress, bound to the user it was
+/// issued for. `take` must remove the entry so a challenge can never be answered twice, and
+/// implementations are expected to expire entries that are never completed.
+#[async_trait]
+pub trait ChallengeStore: Send + Sync {
+    async fn put(
+        &self,
+        binding: &str,
+        user_id: Uuid,
+        state: WebauthnChallengeState,
+    ) -> Result<(), WebauthnError>;
+    async fn take(
+        &self,
+        binding: &str,
+        user_id: Uuid,
+    ) -> Result<WebauthnChallengeState, WebauthnError>;
+}
+
+/// Ties the challenge generation/verification in [`WebauthnAuthenticator`] together with
+/// credential persistence and session issuance, giving the registration-begin/finish and
+/// authentication-begin/finish endpoints a single call each.
+pub struct WebauthnService<'a> {
+    authenticator: &'a WebauthnAuthenticator,
+    credentials: &'a dyn CredentialStore,
+    challenges: &'a dyn ChallengeStore,
+}
+
+impl<'a> WebauthnService<'a> {
+    pub fn new(
+        authenticator: &'a WebauthnAuthenticator,
+        credentials: &'a dyn CredentialStore,
+        challenges: &'a dyn ChallengeStore,
+    ) -> Self {
+        Self {
+            authenticator,
+            credentials,
+            challenges,
+        }
+    }
+
+    /// `POST /webauthn/register/begin`: issue a credential-creation challenge for `user_id`.
+    pub async fn registration_begin(
+        &self,
+        user_id: Uuid,
+        user_name: &str,
+    ) -> Result<(String, CreationChallengeResponse), WebauthnError> {
+        let existing = self.credentials.list_for_user(user_id).await?;
+        let (challenge, state) = self
+            .authenticator
+            .begin_registration(user_id, user_name, &existing)?;
+
+        let binding = generate_challenge_binding();
+        self.challenges.put(&binding, user_id, state).await?;
+
+        Ok((binding, challenge))
+    }
+
+    /// `POST /webauthn/register/finish`: verify the client's attestation and persist the new
+    /// credential.
+    pub async fn registration_finish(
+        &self,
+        user_id: Uuid,
+        name: String,
+        binding: &str,
+        response: &RegisterPublicKeyCredential,
+    ) -> Result<RegisteredCredential, WebauthnError> {
+        let state = self.challenges.take(binding, user_id).await?;
+        let credential = self
+            .authenticator
+            .finish_registration(user_id, name, response, state)?;
+
+        self.credentials.insert(credential.clone()).await?;
+
+        Ok(credential)
+    }
+
+    /// `POST /webauthn/authenticate/begin`: issue an assertion challenge against every credential
+    /// registered for `user_id`.
+    pub async fn authentication_begin(
+        &self,
+        user_id: Uuid,
+    ) -> Result<(String, RequestChallengeResponse), WebauthnError> {
+        let credentials = self.credentials.list_for_user(user_id).await?;
+
+        if credentials.is_empty() {
+            return Err(WebauthnError::NoCredentials);
+        }
+
+        let (challenge, state) = self.authenticator.begin_authentication(&credentials)?;
+
+        let binding = generate_challenge_binding();
+        self.challenges.put(&binding, user_id, state).await?;
+
+        Ok((binding, challenge))
+    }
+
+    /// `POST /webauthn/authenticate/finish`: verify the client's assertion, persist the updated
+    /// signature counter, and issue the same signed session/API key a successful OAuth login
+    /// would produce.
+    pub async fn authentication_finish<const N: usize>(
+        &self,
+        user_id: Uuid,
+        binding: &str,
+        response: &PublicKeyCredential,
+        signer: &dyn Signer,
+    ) -> Result<SignedApiKey, WebauthnError> {
+        let state = self.challenges.take(binding, user_id).await?;
+        let credentials = self.credentials.list_for_user(user_id).await?;
+
+        let credential = credentials
+            .iter()
+            .find(|cred| cred.credential_id == response.raw_id.as_ref())
+            .ok_or(WebauthnError::NoCredentials)?;
+
+        let new_count = self
+            .authenticator
+            .finish_authentication(credential, response, state)?;
+
+        self.credentials
+            .update_signature_count(credential.id, new_count)
+            .await?;
+
+        let key = RawApiKey::generate::<N>(&user_id);
+        Ok(key.sign(signer).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_advanced_accepts_strictly_increasing_counter() {
+        assert!(check_counter_advanced(1, 2).is_ok());
+    }
+
+    #[test]
+    fn test_counter_advanced_accepts_repeated_zero() {
+        // Authenticators that don't implement a signature counter always report 0
+        assert!(check_counter_advanced(0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_counter_advanced_rejects_repeated_nonzero_counter() {
+        assert!(matches!(
+            check_counter_advanced(3, 3),
+            Err(WebauthnError::CounterDidNotIncrease)
+        ));
+    }
+
+    #[test]
+    fn test_counter_advanced_rejects_decreasing_counter() {
+        assert!(matches!(
+            check_counter_advanced(5, 1),
+            Err(WebauthnError::CounterDidNotIncrease)
+        ));
+    }
+}
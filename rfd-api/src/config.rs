@@ -77,9 +77,14 @@ pub enum AsymmetricKey {
         private: String,
         public: String,
     },
-    // Kms {
-    //     id: String,
-    // },
+    /// An asymmetric key held in AWS KMS. Signing and verification are performed by calling KMS
+    /// directly; the private key material never leaves the HSM.
+    Kms {
+        kid: String,
+        key_arn: String,
+        region: String,
+        signing_algorithm: String,
+    },
     Ckms {
         kid: String,
         version: u16,
@@ -94,6 +99,7 @@ impl AsymmetricKey {
     pub fn kid(&self) -> &str {
         match self {
             Self::Local { kid, .. } => kid,
+            Self::Kms { kid, .. } => kid,
             Self::Ckms { kid, .. } => kid,
         }
     }
@@ -108,6 +114,11 @@ pub struct AuthnProviders {
 pub struct OAuthProviders {
     pub github: Option<GitHubOAuthConfig>,
     pub google: Option<GoogleOAuthConfig>,
+    /// Additional OAuth / OIDC providers that are registered by name at startup rather than
+    /// baked in as a dedicated variant. This lets operators point the server at any standards
+    /// compliant issuer (Okta, Keycloak, GitLab, etc) purely through configuration.
+    #[serde(default)]
+    pub oidc: Vec<OidcProviderConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,6 +147,26 @@ pub struct GoogleOAuthWebConfig {
     pub redirect_uri: String,
 }
 
+/// Configuration for a generic, config-driven OIDC provider. Unlike the GitHub and Google
+/// providers, these are not known to the server ahead of time; they are registered under
+/// `OAuthProviderName::Custom(name)` using the endpoints discovered here (or supplied directly,
+/// for issuers that do not expose a `.well-known/openid-configuration` document).
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcProviderConfig {
+    /// The name this provider is registered and referred to under, e.g. `okta` or `keycloak`
+    pub name: String,
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Overrides the endpoint that would otherwise be discovered from the issuer's
+    /// `.well-known/openid-configuration` document
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    pub userinfo_endpoint: Option<String>,
+}
+
 #[derive(Debug, Default, Deserialize)]
 pub struct SearchConfig {
     pub host: String,
@@ -0,0 +1,195 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+pub mod generic;
+pub mod github;
+pub mod google;
+pub mod pkce;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+use uuid::Uuid;
+
+use self::pkce::{CodeChallengeMethod, PkceChallenge, PkceError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OAuthProviderName {
+    GitHub,
+    Google,
+    /// A provider registered at runtime from config, keyed by the name it was configured under
+    Custom(String),
+}
+
+/// The common shape every OAuth/OIDC backend is driven through, whether it is a dedicated
+/// implementation (GitHub, Google) or a config-driven [`generic::GenericOidcProvider`].
+#[async_trait]
+pub trait OAuthProvider: Send + Sync {
+    fn name(&self) -> OAuthProviderName;
+    fn client_id(&self) -> &str;
+    fn client_secret(&self) -> &str;
+    fn scopes(&self) -> &[String];
+    async fn authz_endpoint(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+    async fn token_endpoint(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+    async fn userinfo_endpoint(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[derive(Debug, Error)]
+pub enum LoginAttemptError {
+    #[error("Login attempt has expired")]
+    Expired,
+    #[error("Login attempt has already been used")]
+    AlreadyConsumed,
+    #[error(transparent)]
+    Pkce(#[from] PkceError),
+}
+
+/// Tracks a single in-progress OAuth authorization attempt from the initial redirect through to
+/// token exchange. Only the PKCE *challenge* (never the verifier) is persisted here, so a
+/// database row is all an attacker could read and it still wouldn't let them complete the
+/// exchange. Attempts are single-use and expire with a fixed TTL.
+pub struct LoginAttempt {
+    pub id: Uuid,
+    pub provider: OAuthProviderName,
+    pub code_challenge: String,
+    pub code_challenge_method: CodeChallengeMethod,
+    pub expires_at: DateTime<Utc>,
+    consumed: bool,
+}
+
+impl LoginAttempt {
+    /// Start a new attempt for `provider`, generating a fresh PKCE pair. The returned
+    /// [`PkceChallenge`] carries the verifier that must be handed back to the client that starts
+    /// the flow (the authorization redirect); only the derived challenge is retained on the
+    /// attempt that gets persisted.
+    pub fn new(provider: OAuthProviderName, ttl: Duration) -> (Self, PkceChallenge) {
+        let pkce = PkceChallenge::generate();
+
+        let attempt = Self {
+            id: Uuid::new_v4(),
+            provider,
+            code_challenge: pkce.challenge.clone(),
+            code_challenge_method: pkce.method,
+            expires_at: Utc::now() + ttl,
+            consumed: false,
+        };
+
+        (attempt, pkce)
+    }
+
+    /// Start a new attempt for `provider` using a `code_challenge`/`code_challenge_method` a
+    /// client generated and supplied on its initial authorize request — the standard RFC 7636
+    /// flow, where the verifier never leaves the client until the token exchange. Unlike
+    /// [`Self::new`], this is the path that can actually produce a [`CodeChallengeMethod::Plain`]
+    /// attempt, for a client that can't perform the S256 transform.
+    pub fn new_with_client_challenge(
+        provider: OAuthProviderName,
+        ttl: Duration,
+        code_challenge: String,
+        code_challenge_method: CodeChallengeMethod,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            provider,
+            code_challenge,
+            code_challenge_method,
+            expires_at: Utc::now() + ttl,
+            consumed: false,
+        }
+    }
+
+    /// Validate a `code_verifier` presented during token exchange against this attempt's stored
+    /// challenge, and mark the attempt consumed. Attempts are single-use: calling this a second
+    /// time (even with the correct verifier) always fails, and an expired attempt is rejected
+    /// before the verifier is even checked.
+    pub fn consume(&mut self, code_verifier: &str) -> Result<(), LoginAttemptError> {
+        if self.consumed {
+            return Err(LoginAttemptError::AlreadyConsumed);
+        }
+        self.consumed = true;
+
+        if Utc::now() > self.expires_at {
+            return Err(LoginAttemptError::Expired);
+        }
+
+        pkce::verify(
+            code_verifier,
+            &self.code_challenge,
+            self.code_challenge_method,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consume_succeeds_with_matching_verifier() {
+        let (mut attempt, pkce) = LoginAttempt::new(OAuthProviderName::GitHub, Duration::minutes(10));
+        assert!(attempt.consume(&pkce.verifier).is_ok());
+    }
+
+    #[test]
+    fn test_consume_is_single_use() {
+        let (mut attempt, pkce) = LoginAttempt::new(OAuthProviderName::GitHub, Duration::minutes(10));
+        assert!(attempt.consume(&pkce.verifier).is_ok());
+        assert!(matches!(
+            attempt.consume(&pkce.verifier),
+            Err(LoginAttemptError::AlreadyConsumed)
+        ));
+    }
+
+    #[test]
+    fn test_consume_rejects_expired_attempt() {
+        let (mut attempt, pkce) = LoginAttempt::new(OAuthProviderName::GitHub, Duration::seconds(-1));
+        assert!(matches!(
+            attempt.consume(&pkce.verifier),
+            Err(LoginAttemptError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_consume_rejects_wrong_verifier() {
+        let (mut attempt, _) = LoginAttempt::new(OAuthProviderName::GitHub, Duration::minutes(10));
+        let other = PkceChallenge::generate();
+        assert!(matches!(
+            attempt.consume(&other.verifier),
+            Err(LoginAttemptError::Pkce(_))
+        ));
+    }
+
+    #[test]
+    fn test_consume_succeeds_with_client_supplied_plain_challenge() {
+        let verifier = "a".repeat(64);
+        let mut attempt = LoginAttempt::new_with_client_challenge(
+            OAuthProviderName::GitHub,
+            Duration::minutes(10),
+            verifier.clone(),
+            CodeChallengeMethod::Plain,
+        );
+
+        assert!(attempt.consume(&verifier).is_ok());
+    }
+
+    #[test]
+    fn test_consume_rejects_wrong_verifier_for_client_supplied_challenge() {
+        let verifier = "a".repeat(64);
+        let mut attempt = LoginAttempt::new_with_client_challenge(
+            OAuthProviderName::GitHub,
+            Duration::minutes(10),
+            verifier,
+            CodeChallengeMethod::Plain,
+        );
+
+        assert!(matches!(
+            attempt.consume(&"b".repeat(64)),
+            Err(LoginAttemptError::Pkce(_))
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! RFC 7636 Proof Key for Code Exchange support for the OAuth authorization and device flows.
+//! This allows public clients (CLIs, SPAs) that cannot hold a client secret to prove that the
+//! party exchanging an authorization code for a token is the same party that started the login
+//! attempt.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+const MIN_VERIFIER_LEN: usize = 43;
+const MAX_VERIFIER_LEN: usize = 128;
+
+#[derive(Debug, Error)]
+pub enum PkceError {
+    #[error("code_verifier must be between 43 and 128 characters")]
+    InvalidVerifierLength,
+    #[error("code_verifier does not match the stored code_challenge")]
+    ChallengeMismatch,
+    #[error("Unsupported code_challenge_method: {0}")]
+    UnsupportedMethod(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeChallengeMethod {
+    /// SHA-256 the verifier and base64url (no padding) encode the digest. The only method that
+    /// should be used outside of compatibility shims.
+    S256,
+    /// The challenge is the verifier itself. Supported only as a fallback for clients that can
+    /// not perform the S256 transform.
+    Plain,
+}
+
+impl CodeChallengeMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::S256 => "S256",
+            Self::Plain => "plain",
+        }
+    }
+}
+
+impl TryFrom<&str> for CodeChallengeMethod {
+    type Error = PkceError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "S256" => Ok(Self::S256),
+            "plain" => Ok(Self::Plain),
+            other => Err(PkceError::UnsupportedMethod(other.to_string())),
+        }
+    }
+}
+
+/// A freshly generated PKCE verifier/challenge pair for a login attempt that is just starting.
+/// Only the challenge (and its method) is persisted alongside the attempt; the verifier is
+/// handed to the client and never stored.
+#[derive(Debug, Clone)]
+pub struct PkceChallenge {
+    pub verifier: String,
+    pub challenge: String,
+    pub method: CodeChallengeMethod,
+}
+
+impl PkceChallenge {
+    /// Generate a new high entropy verifier (96 ASCII alphanumeric characters, a subset of the
+    /// unreserved character set from RFC 7636) and derive its S256 challenge.
+    pub fn generate() -> Self {
+        let verifier: String = OsRng
+            .sample_iter(&Alphanumeric)
+            .take(96)
+            .map(char::from)
+            .collect();
+
+        let challenge = derive_challenge(&verifier);
+
+        Self {
+            verifier,
+            challenge,
+            method: CodeChallengeMethod::S256,
+        }
+    }
+}
+
+fn derive_challenge(verifier: &str) -> String {
+    let digest = Sha256::digest(verifier.as_bytes());
+    URL_SAFE_NO_PAD.encode(digest)
+}
+
+/// Validate an incoming `code_verifier` from a token exchange request against the
+/// `code_challenge` (and method) that was persisted when the login attempt was created.
+pub fn verify(
+    verifier: &str,
+    stored_challenge: &str,
+    method: CodeChallengeMethod,
+) -> Result<(), PkceError> {
+    if verifier.len() < MIN_VERIFIER_LEN || verifier.len() > MAX_VERIFIER_LEN {
+        return Err(PkceError::InvalidVerifierLength);
+    }
+
+    let computed_challenge = match method {
+        CodeChallengeMethod::S256 => derive_challenge(verifier),
+        CodeChallengeMethod::Plain => verifier.to_string(),
+    };
+
+    if computed_challenge
+        .as_bytes()
+        .ct_eq(stored_challenge.as_bytes())
+        .into()
+    {
+        Ok(())
+    } else {
+        Err(PkceError::ChallengeMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s256_round_trip_succeeds() {
+        let pkce = PkceChallenge::generate();
+        assert!(verify(&pkce.verifier, &pkce.challenge, pkce.method).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_verifier_is_rejected() {
+        let pkce = PkceChallenge::generate();
+        let other = PkceChallenge::generate();
+        assert!(verify(&other.verifier, &pkce.challenge, pkce.method).is_err());
+    }
+
+    #[test]
+    fn test_plain_method_round_trips() {
+        let verifier = "a".repeat(64);
+        assert!(verify(&verifier, &verifier, CodeChallengeMethod::Plain).is_ok());
+    }
+
+    #[test]
+    fn test_short_verifier_is_rejected() {
+        let pkce = PkceChallenge::generate();
+        assert!(matches!(
+            verify("too-short", &pkce.challenge, pkce.method),
+            Err(PkceError::InvalidVerifierLength)
+        ));
+    }
+}
@@ -17,7 +17,8 @@ use tracing_subscriber::EnvFilter;
 use crate::{
     config::{AppConfig, ServerLogFormat},
     endpoints::login::oauth::{
-        github::GitHubOAuthProvider, google::GoogleOAuthProvider, OAuthProviderName,
+        generic::GenericOidcProvider, github::GitHubOAuthProvider, google::GoogleOAuthProvider,
+        OAuthProviderName,
     },
     initial_data::InitialData,
 };
@@ -110,6 +111,14 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
         )
     }
 
+    for oidc in config.authn.oauth.oidc {
+        let provider_name = oidc.name.clone();
+        context.insert_oauth_provider(
+            OAuthProviderName::Custom(provider_name.clone()),
+            Box::new(move || Box::new(GenericOidcProvider::new(oidc.clone()))),
+        )
+    }
+
     tracing::debug!(?config.spec, "Spec configuration");
 
     let config = ServerConfig {
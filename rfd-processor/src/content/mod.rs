@@ -12,6 +12,7 @@ use rfd_data::{
     RfdNumber,
 };
 use rfd_model::schema_ext::ContentFormat;
+use sha2::{Digest, Sha256};
 use tap::TapFallible;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -20,7 +21,9 @@ use uuid::Uuid;
 
 use crate::{
     github::{GitHubError, GitHubRfdLocation},
+    notifications::{NotificationDispatcher, NotificationError, RfdTransition},
     pdf::RfdPdf,
+    storage::{Storage, StorageError},
     util::{decode_base64, write_file, FileIoError},
 };
 
@@ -42,6 +45,8 @@ pub enum RfdContentError {
     ParserFailed(Result<String, FromUtf8Error>),
     #[error("Failed to run output generator to completion {0}")]
     TaskFailure(#[from] JoinError),
+    #[error("Failed to read or write rendered artifact {0}")]
+    Storage(#[from] StorageError),
 }
 
 #[derive(Debug, Clone)]
@@ -120,39 +125,74 @@ impl<'a> RenderableRfd<'a> {
     }
 
     /// Generate a PDF by combining RFD contents with static resources that are stored for a given
-    /// RFD number on a specific branch. Markdown documents do not support PDF generation
+    /// RFD number on a specific branch. Markdown documents do not support PDF generation. The
+    /// rendered PDF is cached in `storage` keyed by RFD number and a hash of the RFD's source
+    /// text; when that key is already present the cached artifact is returned without re-running
+    /// asciidoctor. Images are not part of the key, so a changed image alone does not cause a new
+    /// render, and concurrent calls that all miss the cache each render and store the PDF.
     pub async fn to_pdf(
         &self,
         client: &Client,
         number: &RfdNumber,
         branch: &GitHubRfdLocation,
+        storage: &dyn Storage,
     ) -> Result<RfdPdf, RfdOutputError> {
         match &self.content {
             RfdContent::Asciidoc(adoc) => {
-                self.download_images(client, number, branch).await?;
+                let key = self.pdf_storage_key(number);
+
+                match storage.get(&key).await {
+                    Ok(contents) => {
+                        return Ok(RfdPdf {
+                            contents,
+                            number: *number,
+                        })
+                    }
+                    Err(StorageError::NotFound(_)) => {}
+                    Err(err) => return Err(RfdContentError::from(err).into()),
+                }
+
+                self.download_images(client, number, branch, storage).await?;
 
                 let pdf = RenderedPdf::render(adoc, self.tmp_path()?).await?;
 
                 self.cleanup_tmp_path()?;
 
+                let contents = pdf.into_inner();
+                storage
+                    .put(&key, contents.clone())
+                    .await
+                    .map_err(RfdContentError::from)?;
+
                 Ok(RfdPdf {
-                    contents: pdf.into_inner(),
+                    contents,
                     number: *number,
                 })
-                // Ok(adoc.to_pdf(client, number, branch).await?)
             }
             _ => Err(RfdOutputError::FormatNotSupported),
         }
     }
 
+    /// The key under which this RFD's rendered PDF is cached in durable storage. Keyed by content
+    /// hash rather than `render_id` (which is fresh per `RenderableRfd` construction) so repeat
+    /// renders of unchanged contents hit the same cache entry.
+    fn pdf_storage_key(&self, number: &RfdNumber) -> String {
+        let digest = Sha256::digest(self.raw().as_bytes());
+        format!("rfd/{}/{}.pdf", number, hex::encode(digest))
+    }
+
     /// Downloads images that are stored on the provided GitHub branch for the given RFD number.
-    /// These are stored locally so in a tmp directory for use by asciidoctor
-    #[instrument(skip(self, client), fields(storage_path = ?self.tmp_path()))]
+    /// Each image is cached in `storage` (shared, durable) keyed by its content hash rather than
+    /// just its path, so an image replaced at the same path in the source repo is fetched fresh
+    /// instead of returning indefinitely-stale bytes; it is written into the local tmp directory
+    /// for use by asciidoctor either way.
+    #[instrument(skip(self, client, storage), fields(storage_path = ?self.tmp_path()))]
     async fn download_images(
         &self,
         client: &Client,
         number: &RfdNumber,
         location: &GitHubRfdLocation,
+        storage: &dyn Storage,
     ) -> Result<(), RfdContentError> {
         let dir = number.repo_path();
         let storage_path = self.tmp_path()?;
@@ -160,15 +200,31 @@ impl<'a> RenderableRfd<'a> {
         let images = location.get_images(client, number).await?;
 
         for image in images {
-            let image_path = storage_path.join(
-                image
-                    .path
-                    .replace(dir.trim_start_matches('/'), "")
-                    .trim_start_matches('/'),
+            let relative_path = image
+                .path
+                .replace(dir.trim_start_matches('/'), "")
+                .trim_start_matches('/')
+                .to_string();
+            let decoded = decode_base64(&image.content)?;
+            let digest = Sha256::digest(&decoded);
+            let cache_key = format!(
+                "rfd/{}/images/{}/{}",
+                number,
+                hex::encode(digest),
+                relative_path
             );
 
-            let path = PathBuf::from(image_path);
-            write_file(&path, &decode_base64(&image.content)?).await?;
+            let contents = match storage.get(&cache_key).await {
+                Ok(cached) => cached,
+                Err(StorageError::NotFound(_)) => {
+                    storage.put(&cache_key, decoded.clone()).await?;
+                    decoded
+                }
+                Err(err) => return Err(RfdContentError::from(err)),
+            };
+
+            let path = PathBuf::from(storage_path.join(&relative_path));
+            write_file(&path, &contents).await?;
 
             tracing::info!(?path, "Wrote embedded image",);
         }
@@ -201,6 +257,72 @@ impl<'a> RenderableRfd<'a> {
 
         Ok(())
     }
+
+    /// Update this RFD's state via [`RfdAttributes::update_state`] and, if the state actually
+    /// changed, enqueue an outbound notification to the linked discussion endpoint through
+    /// `dispatcher`. No-op (and no notification) if `value` matches the current state.
+    pub async fn update_state_notifying(
+        &mut self,
+        value: &str,
+        number: RfdNumber,
+        source: String,
+        dispatcher: &NotificationDispatcher,
+    ) -> Result<(), NotificationError> {
+        let from = self.get_state().map(str::to_string);
+        let changed = from.as_deref() != Some(value);
+
+        self.update_state(value);
+
+        if changed {
+            if let Some(target) = self.get_discussion().map(str::to_string) {
+                dispatcher
+                    .enqueue_transition(
+                        number,
+                        source,
+                        target,
+                        RfdTransition::State {
+                            from,
+                            to: value.to_string(),
+                        },
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update this RFD's discussion link via [`RfdAttributes::update_discussion`] and, if the
+    /// link actually changed, enqueue an outbound notification to the newly linked endpoint
+    /// through `dispatcher`. No-op (and no notification) if `value` matches the current link.
+    pub async fn update_discussion_notifying(
+        &mut self,
+        value: &str,
+        number: RfdNumber,
+        source: String,
+        dispatcher: &NotificationDispatcher,
+    ) -> Result<(), NotificationError> {
+        let from = self.get_discussion().map(str::to_string);
+        let changed = from.as_deref() != Some(value);
+
+        self.update_discussion(value);
+
+        if changed {
+            dispatcher
+                .enqueue_transition(
+                    number,
+                    source,
+                    value.to_string(),
+                    RfdTransition::Discussion {
+                        from,
+                        to: value.to_string(),
+                    },
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> RfdAttributes for RenderableRfd<'a> {
@@ -295,3 +417,32 @@ pub enum RfdOutputError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::notifications::InMemoryNotificationQueue;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_discussion_notifying_enqueues_through_a_real_dispatcher() {
+        let mut rfd = RenderableRfd::new_asciidoc("= An RFD\n\nNo discussion link yet.\n");
+        let dispatcher = NotificationDispatcher::new(vec![], Box::new(InMemoryNotificationQueue::new()));
+
+        rfd.update_discussion_notifying(
+            "https://github.com/oxidecomputer/rfd/pull/1",
+            RfdNumber::from(1),
+            "https://rfd.shared.oxide.computer/rfd/0001".to_string(),
+            &dispatcher,
+        )
+        .await
+        .unwrap();
+
+        // `deliver_next` finds no registered notifier for the enqueued target, confirming the
+        // transition really was queued (rather than silently dropped) by the call above.
+        assert!(matches!(
+            dispatcher.deliver_next().await,
+            Err(NotificationError::EndpointDiscovery(_))
+        ));
+    }
+}
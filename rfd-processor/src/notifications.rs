@@ -0,0 +1,361 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Outbound, webmention-style notifications sent to the endpoint backing an RFD's discussion
+//! link whenever that RFD's state or discussion link transitions. Delivery is queued rather than
+//! performed inline so a slow or unreachable endpoint cannot hold up processing. The queue sits
+//! behind [`NotificationQueue`]; the only implementation in this module,
+//! [`InMemoryNotificationQueue`], keeps pending notifications in memory, so they do not survive
+//! a restart.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rfd_data::RfdNumber;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("Failed to discover a notification endpoint for {0}")]
+    EndpointDiscovery(String),
+    #[error("Notification delivery failed: {0}")]
+    Delivery(#[from] reqwest::Error),
+    #[error("Persisted notification queue failure: {0}")]
+    Queue(String),
+}
+
+/// The change that triggered a notification. Mirrors the transitions `RfdAttributes` exposes:
+/// an RFD's lifecycle state and the link to wherever its discussion happens (a GitHub PR, a chat
+/// thread, etc).
+#[derive(Debug, Clone)]
+pub enum RfdTransition {
+    State { from: Option<String>, to: String },
+    Discussion { from: Option<String>, to: String },
+}
+
+/// A single outbound notification, queued for delivery to the endpoint discovered for the
+/// linked discussion URL. `not_before` lets a failed delivery be rescheduled without blocking the
+/// worker loop: [`NotificationQueue::dequeue`] implementations must only return notifications
+/// whose `not_before` has already passed.
+#[derive(Debug, Clone)]
+pub struct DiscussionNotification {
+    pub rfd: RfdNumber,
+    pub source: String,
+    pub target: String,
+    pub transition: RfdTransition,
+    pub attempts: u32,
+    pub not_before: DateTime<Utc>,
+}
+
+/// Discovers the notification endpoint for a discussion URL and delivers a source/target
+/// notification to it. Implementations exist per discussion backend (GitHub PR comments, a chat
+/// webhook, etc) so a new backend can be added without touching the queue or retry logic.
+#[async_trait]
+pub trait DiscussionNotifier: Send + Sync {
+    /// Whether this notifier knows how to handle the given discussion URL
+    fn handles(&self, target: &str) -> bool;
+
+    /// Resolve `target` to the concrete endpoint that should receive the ping
+    async fn discover_endpoint(&self, target: &str) -> Result<String, NotificationError>;
+
+    /// POST the source/target notification to the discovered endpoint
+    async fn notify(
+        &self,
+        endpoint: &str,
+        notification: &DiscussionNotification,
+    ) -> Result<(), NotificationError>;
+}
+
+/// Where pending notifications are held between delivery attempts. A durable implementation
+/// (backed by the database) is expected in production so delivery survives a restart; an
+/// in-memory implementation is useful for tests. Implementations must treat `not_before` as a
+/// filter: a notification scheduled for the future must not be returned by `dequeue` until then,
+/// so one slow/backed-off notification never blocks the rest of the queue.
+#[async_trait]
+pub trait NotificationQueue: Send + Sync {
+    async fn enqueue(&self, notification: DiscussionNotification) -> Result<(), NotificationError>;
+    async fn dequeue(&self) -> Result<Option<DiscussionNotification>, NotificationError>;
+    async fn requeue(&self, notification: DiscussionNotification) -> Result<(), NotificationError>;
+}
+
+const MAX_ATTEMPTS: u32 = 8;
+const BASE_BACKOFF_SECS: i64 = 5;
+
+/// Dispatches queued notifications to whichever registered [`DiscussionNotifier`] handles the
+/// notification's target URL, retrying with exponential backoff on failure until `MAX_ATTEMPTS`
+/// is reached.
+pub struct NotificationDispatcher {
+    notifiers: Vec<Box<dyn DiscussionNotifier>>,
+    queue: Box<dyn NotificationQueue>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(
+        notifiers: Vec<Box<dyn DiscussionNotifier>>,
+        queue: Box<dyn NotificationQueue>,
+    ) -> Self {
+        Self { notifiers, queue }
+    }
+
+    /// Enqueue a notification for an RFD whose state or discussion link just transitioned. This
+    /// is the entry point processors should call after observing a change via `RfdAttributes`.
+    pub async fn enqueue_transition(
+        &self,
+        rfd: RfdNumber,
+        source: String,
+        target: String,
+        transition: RfdTransition,
+    ) -> Result<(), NotificationError> {
+        self.queue
+            .enqueue(DiscussionNotification {
+                rfd,
+                source,
+                target,
+                transition,
+                attempts: 0,
+                not_before: Utc::now(),
+            })
+            .await
+    }
+
+    /// Drain and deliver a single ready queued notification, rescheduling it with exponential
+    /// backoff on failure instead of blocking this call until the backoff elapses. Intended to be
+    /// called in a tight loop by a background worker; a failing target delays only its own next
+    /// attempt, never the notifications behind it in the queue.
+    pub async fn deliver_next(&self) -> Result<(), NotificationError> {
+        let Some(mut notification) = self.queue.dequeue().await? else {
+            return Ok(());
+        };
+
+        let notifier = self
+            .notifiers
+            .iter()
+            .find(|notifier| notifier.handles(&notification.target))
+            .ok_or_else(|| NotificationError::EndpointDiscovery(notification.target.clone()))?;
+
+        let result = match notifier.discover_endpoint(&notification.target).await {
+            Ok(endpoint) => notifier.notify(&endpoint, &notification).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) if notification.attempts + 1 >= MAX_ATTEMPTS => Err(err),
+            Err(_) => {
+                notification.attempts += 1;
+                notification.not_before =
+                    Utc::now() + Duration::seconds(BASE_BACKOFF_SECS * 2i64.pow(notification.attempts));
+                self.queue.requeue(notification).await
+            }
+        }
+    }
+}
+
+/// Delivers notifications to a GitHub pull request's discussion by posting an issue comment
+/// through the REST API. Handles any `target` shaped like a PR URL
+/// (`https://github.com/{owner}/{repo}/pull/{number}`).
+pub struct GitHubPrNotifier {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl GitHubPrNotifier {
+    pub fn new(client: reqwest::Client, token: String) -> Self {
+        Self { client, token }
+    }
+}
+
+/// Pulls `(owner, repo, pr_number)` out of a GitHub PR URL, or `None` if `target` isn't one.
+fn parse_pr_url(target: &str) -> Option<(&str, &str, &str)> {
+    let rest = target.strip_prefix("https://github.com/")?;
+    let mut segments = rest.trim_end_matches('/').splitn(4, '/');
+
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    let kind = segments.next()?;
+    let number = segments.next()?;
+
+    (kind == "pull").then_some((owner, repo, number))
+}
+
+#[async_trait]
+impl DiscussionNotifier for GitHubPrNotifier {
+    fn handles(&self, target: &str) -> bool {
+        parse_pr_url(target).is_some()
+    }
+
+    async fn discover_endpoint(&self, target: &str) -> Result<String, NotificationError> {
+        let (owner, repo, number) = parse_pr_url(target)
+            .ok_or_else(|| NotificationError::EndpointDiscovery(target.to_string()))?;
+
+        Ok(format!(
+            "https://api.github.com/repos/{owner}/{repo}/issues/{number}/comments"
+        ))
+    }
+
+    async fn notify(
+        &self,
+        endpoint: &str,
+        notification: &DiscussionNotification,
+    ) -> Result<(), NotificationError> {
+        let body = match &notification.transition {
+            RfdTransition::State { from, to } => format!(
+                "RFD {} state changed{} to **{}**",
+                notification.rfd,
+                from.as_deref()
+                    .map(|from| format!(" from `{from}`"))
+                    .unwrap_or_default(),
+                to
+            ),
+            RfdTransition::Discussion { from, to } => format!(
+                "RFD {} discussion link changed{} to {}",
+                notification.rfd,
+                from.as_deref()
+                    .map(|from| format!(" from {from}"))
+                    .unwrap_or_default(),
+                to
+            ),
+        };
+
+        self.client
+            .post(endpoint)
+            .bearer_auth(&self.token)
+            .header("User-Agent", "rfd-processor")
+            .json(&serde_json::json!({ "body": body }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// An in-process, non-durable [`NotificationQueue`]. Useful for tests, and for a single-instance
+/// deployment that can tolerate losing pending notifications across a restart; a horizontally
+/// scaled deployment should back this with the database instead.
+#[derive(Default)]
+pub struct InMemoryNotificationQueue {
+    notifications: Mutex<Vec<DiscussionNotification>>,
+}
+
+impl InMemoryNotificationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl NotificationQueue for InMemoryNotificationQueue {
+    async fn enqueue(&self, notification: DiscussionNotification) -> Result<(), NotificationError> {
+        self.notifications.lock().unwrap().push(notification);
+        Ok(())
+    }
+
+    async fn dequeue(&self) -> Result<Option<DiscussionNotification>, NotificationError> {
+        let mut notifications = self.notifications.lock().unwrap();
+        let now = Utc::now();
+        let ready = notifications.iter().position(|n| n.not_before <= now);
+
+        Ok(ready.map(|index| notifications.remove(index)))
+    }
+
+    async fn requeue(&self, notification: DiscussionNotification) -> Result<(), NotificationError> {
+        self.notifications.lock().unwrap().push(notification);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pr_url_extracts_owner_repo_and_number() {
+        assert_eq!(
+            parse_pr_url("https://github.com/oxidecomputer/rfd/pull/42"),
+            Some(("oxidecomputer", "rfd", "42"))
+        );
+    }
+
+    #[test]
+    fn test_parse_pr_url_rejects_non_pr_urls() {
+        assert_eq!(
+            parse_pr_url("https://github.com/oxidecomputer/rfd/issues/42"),
+            None
+        );
+        assert_eq!(parse_pr_url("https://example.com/not-github"), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_queue_only_dequeues_ready_notifications() {
+        let queue = InMemoryNotificationQueue::new();
+        let mut notification = DiscussionNotification {
+            rfd: RfdNumber::from(1),
+            source: "https://example.com/rfd/1".to_string(),
+            target: "https://github.com/oxidecomputer/rfd/pull/1".to_string(),
+            transition: RfdTransition::State {
+                from: None,
+                to: "published".to_string(),
+            },
+            attempts: 0,
+            not_before: Utc::now() + Duration::seconds(60),
+        };
+
+        queue.enqueue(notification.clone()).await.unwrap();
+        assert!(queue.dequeue().await.unwrap().is_none());
+
+        notification.not_before = Utc::now();
+        queue.enqueue(notification).await.unwrap();
+        assert!(queue.dequeue().await.unwrap().is_some());
+    }
+
+    struct NoopNotifier;
+
+    #[async_trait]
+    impl DiscussionNotifier for NoopNotifier {
+        fn handles(&self, _target: &str) -> bool {
+            true
+        }
+
+        async fn discover_endpoint(&self, target: &str) -> Result<String, NotificationError> {
+            Ok(target.to_string())
+        }
+
+        async fn notify(
+            &self,
+            _endpoint: &str,
+            _notification: &DiscussionNotification,
+        ) -> Result<(), NotificationError> {
+            Err(NotificationError::EndpointDiscovery("always fails".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_backoff_increases_and_schedules_into_the_future() {
+        let now = Utc::now();
+        let mut notification = DiscussionNotification {
+            rfd: RfdNumber::from(1),
+            source: "https://example.com/rfd/1".to_string(),
+            target: "https://example.com/discussion/1".to_string(),
+            transition: RfdTransition::State {
+                from: Some("draft".to_string()),
+                to: "published".to_string(),
+            },
+            attempts: 0,
+            not_before: now,
+        };
+
+        notification.attempts += 1;
+        notification.not_before =
+            now + Duration::seconds(BASE_BACKOFF_SECS * 2i64.pow(notification.attempts));
+        let first_backoff = notification.not_before;
+        assert!(first_backoff > now);
+
+        notification.attempts += 1;
+        notification.not_before =
+            now + Duration::seconds(BASE_BACKOFF_SECS * 2i64.pow(notification.attempts));
+        assert!(notification.not_before > first_backoff);
+    }
+}
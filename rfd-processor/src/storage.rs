@@ -0,0 +1,292 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A pluggable destination for the artifacts generated while rendering an RFD (downloaded
+//! images, rendered PDFs). The filesystem implementation keeps artifacts as files under a
+//! configured root; the object store implementation lets horizontally scaled API instances
+//! share rendered output instead of regenerating it per instance. Rendering itself still works
+//! in a per-render `env::temp_dir()` directory that asciidoctor reads from.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Artifact not found for key {0}")]
+    NotFound(String),
+    #[error("Filesystem storage failure: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Object store request failed: {0}")]
+    ObjectStore(String),
+}
+
+/// A minimal async key/value store for rendered artifacts. Keys are opaque, slash-separated
+/// paths, e.g. `rfd/0123/image.png` or `rfd/0123/render.pdf`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Stores artifacts as plain files under a root directory, preserving the key as a relative
+/// path. It holds the cached artifacts only; the per-render `env::temp_dir()` directory used
+/// while rendering is separate from it.
+pub struct FilesystemStorage {
+    root: PathBuf,
+}
+
+impl FilesystemStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+#[async_trait]
+impl Storage for FilesystemStorage {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path_for(key);
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(path, data).await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        tokio::fs::read(self.path_for(key))
+            .await
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+                _ => StorageError::Io(err),
+            })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path_for(key)).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(StorageError::Io(err)),
+        }
+    }
+}
+
+/// Stores artifacts in an S3 compatible object store (this also covers GCS via its S3
+/// interoperability API), keyed by an optional prefix plus the artifact key.
+pub struct S3Storage {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: Option<String>,
+}
+
+impl S3Storage {
+    pub fn new(client: aws_sdk_s3::Client, bucket: String, prefix: Option<String>) -> Self {
+        Self {
+            client,
+            bucket,
+            prefix,
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), key),
+            None => key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .body(data.into())
+            .send()
+            .await
+            .map_err(|err| StorageError::ObjectStore(err.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await
+            .map_err(|err| match err.into_service_error() {
+                aws_sdk_s3::operation::get_object::GetObjectError::NoSuchKey(_) => {
+                    StorageError::NotFound(key.to_string())
+                }
+                other => StorageError::ObjectStore(other.to_string()),
+            })?;
+
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|err| StorageError::ObjectStore(err.to_string()))?;
+
+        Ok(bytes.into_bytes().to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await
+            .map_err(|err| StorageError::ObjectStore(err.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Selects and configures which [`Storage`] backend this processor's `storage` config section
+/// picks out, so an operator can choose `FilesystemStorage` (the default, single-instance
+/// behavior) or `S3Storage` (for horizontally scaled deployments that need to share rendered
+/// output) without a code change.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum StorageConfig {
+    Filesystem {
+        root: PathBuf,
+    },
+    S3 {
+        bucket: String,
+        region: String,
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+}
+
+impl StorageConfig {
+    /// Build the configured `Storage` backend. Building an `S3Storage` loads AWS credentials
+    /// from the environment for the configured region, mirroring how `KmsSigner::from_config`
+    /// builds its KMS client.
+    pub async fn build(&self) -> Box<dyn Storage> {
+        match self {
+            StorageConfig::Filesystem { root } => Box::new(FilesystemStorage::new(root.clone())),
+            StorageConfig::S3 {
+                bucket,
+                region,
+                prefix,
+            } => {
+                let sdk_config = aws_config::from_env()
+                    .region(aws_sdk_s3::config::Region::new(region.clone()))
+                    .load()
+                    .await;
+
+                Box::new(S3Storage::new(
+                    aws_sdk_s3::Client::new(&sdk_config),
+                    bucket.clone(),
+                    prefix.clone(),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_filesystem_storage_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemStorage::new(dir.path().to_path_buf());
+
+        storage
+            .put("rfd/1/render.pdf", b"hello".to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage.get("rfd/1/render.pdf").await.unwrap(),
+            b"hello".to_vec()
+        );
+
+        storage.delete("rfd/1/render.pdf").await.unwrap();
+
+        assert!(matches!(
+            storage.get("rfd/1/render.pdf").await,
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_delete_of_missing_key_is_not_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemStorage::new(dir.path().to_path_buf());
+
+        assert!(storage.delete("rfd/does-not-exist.pdf").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_get_of_missing_key_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemStorage::new(dir.path().to_path_buf());
+
+        assert!(matches!(
+            storage.get("rfd/does-not-exist.pdf").await,
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_storage_config_builds_filesystem_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StorageConfig::Filesystem {
+            root: dir.path().to_path_buf(),
+        };
+        let storage = config.build().await;
+
+        storage.put("rfd/1/render.pdf", b"hello".to_vec()).await.unwrap();
+        assert_eq!(storage.get("rfd/1/render.pdf").await.unwrap(), b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_s3_object_key_without_prefix() {
+        let storage = S3Storage::new(s3_test_client(), "bucket".to_string(), None);
+        assert_eq!(storage.object_key("rfd/1/render.pdf"), "rfd/1/render.pdf");
+    }
+
+    #[test]
+    fn test_s3_object_key_with_prefix() {
+        let storage =
+            S3Storage::new(s3_test_client(), "bucket".to_string(), Some("artifacts/".to_string()));
+        assert_eq!(
+            storage.object_key("rfd/1/render.pdf"),
+            "artifacts/rfd/1/render.pdf"
+        );
+    }
+
+    fn s3_test_client() -> aws_sdk_s3::Client {
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new("us-east-1"))
+            .credentials_provider(aws_sdk_s3::config::Credentials::new(
+                "test", "test", None, None, "test",
+            ))
+            .build();
+
+        aws_sdk_s3::Client::from_conf(config)
+    }
+}